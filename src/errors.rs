@@ -44,6 +44,24 @@ pub enum Errors {
 
     #[error("Unsupport http method: '{}'", method)]
     UnsupportedHttpMethodError { method: String },
+
+    #[error("Could not resolve reference: '{reference}'")]
+    #[diagnostic(
+        code(openapi::unresolved_reference),
+        help("Check that the $ref points to an existing component in the spec and is not part of a cycle")
+    )]
+    UnresolvedReferenceError { reference: String },
+
+    #[error("Request failed after {attempts} attempt(s): {source}")]
+    #[diagnostic(
+        code(request::retries_exhausted),
+        help("Increase --timeout/--connect-timeout or --retries, or check connectivity to the server")
+    )]
+    RequestRetriesExhaustedError {
+        attempts: u32,
+        #[source]
+        source: reqwest::Error,
+    },
 }
 
 impl From<reqwest::header::InvalidHeaderName> for Errors {
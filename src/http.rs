@@ -1,13 +1,111 @@
+use base64::engine::general_purpose;
+use base64::Engine;
 use miette::miette;
-use percent_encoding::percent_decode_str;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use reqwest::header::HeaderMap;
 use reqwest::Client;
 use serde_json::Value;
+use std::time::Duration;
 use tracing::{debug, error, info, warn};
 use url::Url;
 
 use crate::{errors::Errors, openapi};
 
+// RFC 3986 unreserved characters are left alone; everything else (including `/`, `?`, `#` and
+// spaces) gets percent-encoded so an interpolated path parameter can't introduce extra path
+// segments or otherwise corrupt the URL structure.
+const PATH_SEGMENT_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+// Reads a parameter's value out of clap's matches using the value type derived from its schema,
+// then renders it to the string form used for path/query/header/cookie interpolation.
+fn param_value_as_string(param: &openapi::Parameter, matches: &clap::ArgMatches) -> Option<String> {
+    match param.param_type {
+        openapi::ParamType::Integer => matches
+            .get_one::<i64>(param.name.as_str())
+            .map(i64::to_string),
+        openapi::ParamType::Number => matches
+            .get_one::<f64>(param.name.as_str())
+            .map(f64::to_string),
+        openapi::ParamType::Boolean => matches
+            .get_one::<bool>(param.name.as_str())
+            .map(bool::to_string),
+        openapi::ParamType::String | openapi::ParamType::Enum(_) | openapi::ParamType::Json => {
+            matches.get_one::<String>(param.name.as_str()).cloned()
+        }
+    }
+}
+
+// The env var a security scheme's credential falls back to when its `--<scheme>-token` flag is
+// not passed, e.g. scheme "apiKey" -> APIKEY_TOKEN.
+pub fn security_env_var_name(scheme_name: &str) -> String {
+    format!(
+        "{}_TOKEN",
+        scheme_name
+            .to_uppercase()
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    )
+}
+
+// Applies only the security schemes the spec actually declared for this endpoint, reading the
+// credential from its `--<scheme>-token` flag or else the scheme's env var.
+fn apply_security(
+    security: &[openapi::SecurityBinding],
+    matches: &clap::ArgMatches,
+    final_url: &mut Url,
+    headers: &mut HeaderMap,
+    cookies: &mut Vec<(String, String)>,
+) -> miette::Result<(), Errors> {
+    for binding in security {
+        let flag_name = format!("{}-token", binding.scheme_name);
+        let value = matches
+            .get_one::<String>(flag_name.as_str())
+            .cloned()
+            .or_else(|| std::env::var(security_env_var_name(&binding.scheme_name)).ok());
+
+        let Some(value) = value else {
+            continue;
+        };
+
+        match &binding.kind {
+            openapi::SecurityKind::HttpBasic => {
+                // RFC 7617 requires the "user:pass" credential to be base64-encoded before it's
+                // placed in the header, not passed through verbatim.
+                let encoded = general_purpose::STANDARD.encode(value.as_bytes());
+                headers.insert(
+                    reqwest::header::AUTHORIZATION,
+                    reqwest::header::HeaderValue::from_str(&format!("Basic {}", encoded))?,
+                );
+            }
+            openapi::SecurityKind::HttpBearer => {
+                headers.insert(
+                    reqwest::header::AUTHORIZATION,
+                    reqwest::header::HeaderValue::from_str(&format!("Bearer {}", value))?,
+                );
+            }
+            openapi::SecurityKind::ApiKey { location, key_name } => match location {
+                openapi::ApiKeyLocation::Header => {
+                    headers.insert(
+                        reqwest::header::HeaderName::from_bytes(key_name.as_bytes())?,
+                        reqwest::header::HeaderValue::from_str(&value)?,
+                    );
+                }
+                openapi::ApiKeyLocation::Query => {
+                    final_url.query_pairs_mut().append_pair(key_name, &value);
+                }
+                openapi::ApiKeyLocation::Cookie => {
+                    cookies.push((key_name.clone(), value));
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
 fn join_url(base: &str, path: &str) -> Result<Url, url::ParseError> {
     let base_url = Url::parse(base)?;
     let path_without_leading_slash = path.strip_prefix('/').unwrap_or(path);
@@ -37,15 +135,18 @@ fn handle_url_path(
     let mut path_for_interpolation = url.path().to_string();
     info!("Process path parameters");
     for param in endpoint.params.clone() {
-        if let Some(value) = matches.get_one::<String>(param.name.as_str()) {
+        if let Some(value) = param_value_as_string(&param, &matches) {
             if matches!(param.location, openapi::ParameterLocation::Path) {
                 // First decode any percent-encoded characters in the path
                 let decoded_path = percent_decode_str(&path_for_interpolation)
                     .decode_utf8_lossy()
                     .into_owned();
-                // Then do the replacement
+                // Then do the replacement, percent-encoding the value so reserved characters
+                // (e.g. a literal `/`) can't be mistaken for path structure
+                let encoded_value =
+                    utf8_percent_encode(&value, PATH_SEGMENT_ENCODE_SET).to_string();
                 path_for_interpolation =
-                    decoded_path.replace(&format!("{{{}}}", param.name), value);
+                    decoded_path.replace(&format!("{{{}}}", param.name), &encoded_value);
             }
         } else {
             // If the parameter is required, we should return an error
@@ -64,37 +165,67 @@ fn handle_url_path(
     Ok(url)
 }
 
+// The parsed body of a response: JSON when `Content-Type` says so, otherwise the raw bytes
+// untouched so binary downloads (images, PDFs, octet-stream, ...) aren't mangled by a lossy
+// UTF-8 conversion.
+#[derive(Debug)]
+pub enum ResponseBody {
+    Json(Value),
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug)]
+pub struct ResponseOutput {
+    pub status: reqwest::StatusCode,
+    pub headers: HeaderMap,
+    pub body: ResponseBody,
+}
+
+fn content_type_is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|content_type| {
+            let essence = content_type.split(';').next().unwrap_or("").trim();
+            essence == "application/json" || essence.ends_with("+json")
+        })
+        .unwrap_or(false)
+}
+
 pub async fn execute_request(
     endpoint: openapi::Endpoint,
     matches: clap::ArgMatches,
     base_url: &str,
-) -> miette::Result<Value, Errors> {
+    client: &Client,
+    retries: u32,
+) -> miette::Result<ResponseOutput, Errors> {
     let mut final_url = handle_url_path(endpoint.clone(), matches.clone(), base_url)?;
 
-    // Create HTTP client
-    let client = Client::new();
-
     // Process query and body parameters
     let mut body: Option<Value> = None;
 
     let mut headers = HeaderMap::new();
+    let mut cookies: Vec<(String, String)> = Vec::new();
     for param in endpoint.params {
-        if let Some(value) = matches.get_one::<String>(param.name.as_str()) {
+        if let Some(value) = param_value_as_string(&param, &matches) {
             match param.location {
                 openapi::ParameterLocation::Query => {
-                    final_url.query_pairs_mut().append_pair(&param.name, value);
+                    final_url.query_pairs_mut().append_pair(&param.name, &value);
                 }
                 openapi::ParameterLocation::Body => {
-                    body = Some(serde_json::from_str(value)?);
+                    body = Some(serde_json::from_str(&value)?);
                 }
                 openapi::ParameterLocation::Header => {
                     headers.insert(
                         reqwest::header::HeaderName::from_bytes(
                             param.name.to_uppercase().as_bytes(),
                         )?,
-                        reqwest::header::HeaderValue::from_str(value)?,
+                        reqwest::header::HeaderValue::from_str(&value)?,
                     );
                 }
+                openapi::ParameterLocation::Cookie => {
+                    cookies.push((param.name.clone(), value));
+                }
                 openapi::ParameterLocation::Path => {
                     // Already handled above
                     continue;
@@ -103,19 +234,31 @@ pub async fn execute_request(
         }
     }
 
-    if let Ok(token) = std::env::var("AUTHORIZATION_BASIC_TOKEN") {
-        headers.insert(
-            reqwest::header::AUTHORIZATION,
-            reqwest::header::HeaderValue::from_str(format!("Basic {}", token).as_str())?,
-        );
-    };
+    apply_security(
+        &endpoint.security,
+        &matches,
+        &mut final_url,
+        &mut headers,
+        &mut cookies,
+    )?;
 
-    if let Ok(token) = std::env::var("AUTHORIZATION_BEARER_TOKEN") {
+    if !cookies.is_empty() {
+        let cookie_header = cookies
+            .iter()
+            .map(|(name, value)| {
+                format!(
+                    "{}={}",
+                    name,
+                    utf8_percent_encode(value, NON_ALPHANUMERIC)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
         headers.insert(
-            reqwest::header::AUTHORIZATION,
-            reqwest::header::HeaderValue::from_str(format!("Bearer {}", token).as_str())?,
+            reqwest::header::COOKIE,
+            reqwest::header::HeaderValue::from_str(&cookie_header)?,
         );
-    };
+    }
 
     // Build the request based on the HTTP method
     let mut request = match endpoint.method.to_lowercase().as_str() {
@@ -123,6 +266,10 @@ pub async fn execute_request(
         "post" => client.post(final_url),
         "put" => client.put(final_url),
         "delete" => client.delete(final_url),
+        "patch" => client.patch(final_url),
+        "head" => client.head(final_url),
+        "options" => client.request(reqwest::Method::OPTIONS, final_url),
+        "trace" => client.request(reqwest::Method::TRACE, final_url),
         method => {
             error!("unsupported method {}", method.to_string());
             return Err(Errors::UnsupportedHttpMethodError {
@@ -138,9 +285,110 @@ pub async fn execute_request(
         request = request.json(&body_value);
     }
 
-    // Send the request and parse the response
-    let response = request.send().await?;
-    let result = response.json().await?;
+    // Send the request, retrying transient (timeout/connection) failures with exponential backoff
+    let mut last_err = None;
+    let mut sent = None;
+    for attempt in 0..=retries {
+        let attempt_request = request
+            .try_clone()
+            .expect("request body must support retries");
+        match attempt_request.send().await {
+            Ok(response) => {
+                sent = Some(response);
+                break;
+            }
+            Err(err) if err.is_timeout() || err.is_connect() => {
+                warn!("request attempt {} failed: {}", attempt + 1, err);
+                last_err = Some(err);
+                if attempt < retries {
+                    let backoff = Duration::from_millis(200u64.saturating_mul(1u64 << attempt.min(63)))
+                        .min(Duration::from_secs(30));
+                    tokio::time::sleep(backoff).await;
+                }
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let response = match sent {
+        Some(response) => response,
+        None => {
+            return Err(Errors::RequestRetriesExhaustedError {
+                attempts: retries + 1,
+                source: last_err.expect("loop runs at least once"),
+            })
+        }
+    };
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    let is_json = content_type_is_json(&headers);
+    let bytes = response.bytes().await?;
+
+    let body = if is_json {
+        if bytes.iter().all(|b| b.is_ascii_whitespace()) {
+            ResponseBody::Json(Value::Null)
+        } else {
+            ResponseBody::Json(serde_json::from_slice(&bytes)?)
+        }
+    } else {
+        ResponseBody::Bytes(bytes.to_vec())
+    };
+
+    Ok(ResponseOutput {
+        status,
+        headers,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openapi::{Endpoint, ParamType, Parameter, ParameterLocation};
+
+    fn id_endpoint() -> Endpoint {
+        Endpoint {
+            name: "get_thing".to_string(),
+            method: "get".to_string(),
+            path: "/things/{id}".to_string(),
+            params: vec![Parameter {
+                name: "id".to_string(),
+                location: ParameterLocation::Path,
+                required: true,
+                param_type: ParamType::String,
+            }],
+            security: Vec::new(),
+        }
+    }
+
+    fn matches_for(value: &str) -> clap::ArgMatches {
+        clap::Command::new("test")
+            .arg(clap::Arg::new("id"))
+            .get_matches_from(vec!["test", value])
+    }
+
+    #[test]
+    fn encodes_embedded_slash_in_path_parameter() {
+        let matches = matches_for("a/b");
+        let url = handle_url_path(id_endpoint(), matches, "http://localhost:3000").unwrap();
+        assert_eq!(url.path(), "/things/a%2Fb");
+        assert_eq!(url.path_segments().unwrap().count(), 2);
+    }
 
-    Ok(result)
+    #[test]
+    fn encodes_embedded_space_in_path_parameter() {
+        let matches = matches_for("a b");
+        let url = handle_url_path(id_endpoint(), matches, "http://localhost:3000").unwrap();
+        assert_eq!(url.path(), "/things/a%20b");
+        assert_eq!(url.path_segments().unwrap().count(), 2);
+    }
+
+    #[test]
+    fn encodes_unicode_in_path_parameter() {
+        let matches = matches_for("café");
+        let url = handle_url_path(id_endpoint(), matches, "http://localhost:3000").unwrap();
+        assert_eq!(url.path(), "/things/caf%C3%A9");
+        assert_eq!(url.path_segments().unwrap().count(), 2);
+    }
 }
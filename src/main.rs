@@ -1,6 +1,7 @@
 use std::io::Write;
 
 use crate::errors::Errors;
+use clap::builder::{BoolishValueParser, PossibleValuesParser};
 use clap::{Arg, Command};
 use tracing::{debug, error, info, warn, Level};
 use tracing_subscriber::EnvFilter;
@@ -15,6 +16,9 @@ const DEFAULT_SERVER: &str = "http://localhost:3000";
 struct InitialConfig {
     config: String,
     server: Option<String>,
+    timeout_secs: Option<u64>,
+    connect_timeout_secs: Option<u64>,
+    retries: u32,
 }
 
 impl Default for InitialConfig {
@@ -22,6 +26,9 @@ impl Default for InitialConfig {
         InitialConfig {
             config: DEFAULT_CONFIG.to_string(),
             server: None,
+            timeout_secs: None,
+            connect_timeout_secs: None,
+            retries: 0,
         }
     }
 }
@@ -58,6 +65,37 @@ fn build_cli(mut endpoints: Vec<openapi::Endpoint>) -> Command {
                 .long("server")
                 .required(false)
                 .help("override server from openapi config file"),
+        )
+        .arg(
+            Arg::new("include")
+                .short('i')
+                .long("include")
+                .required(false)
+                .global(true)
+                .action(clap::ArgAction::SetTrue)
+                .help("Include the response status line and headers in the output"),
+        )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .required(false)
+                .value_parser(clap::value_parser!(u64))
+                .help("Request timeout in seconds"),
+        )
+        .arg(
+            Arg::new("connect-timeout")
+                .long("connect-timeout")
+                .required(false)
+                .value_parser(clap::value_parser!(u64))
+                .help("Connection timeout in seconds"),
+        )
+        .arg(
+            Arg::new("retries")
+                .long("retries")
+                .required(false)
+                .value_parser(clap::value_parser!(u32))
+                .default_value("0")
+                .help("Number of times to retry a request on timeout or connection error"),
         );
     // Register `complete` subcommand
     command = clap_autocomplete::add_subcommand(command);
@@ -65,6 +103,7 @@ fn build_cli(mut endpoints: Vec<openapi::Endpoint>) -> Command {
     endpoints.sort_by_key(|e| e.name.clone());
     for endpoint in endpoints {
         let mut cmd = Command::new(endpoint.name);
+        let mut has_numeric_param = false;
 
         for param in endpoint.params {
             let mut arg = Arg::new(param.name.to_owned()); // Use string slice directly
@@ -76,11 +115,56 @@ fn build_cli(mut endpoints: Vec<openapi::Endpoint>) -> Command {
                 openapi::ParameterLocation::Body => arg.help("JSON string for request body"),
                 openapi::ParameterLocation::Path => arg,
                 openapi::ParameterLocation::Header => arg.long(param.name),
+                openapi::ParameterLocation::Cookie => arg.long(param.name),
+            };
+
+            arg = match param.param_type {
+                openapi::ParamType::Integer => {
+                    has_numeric_param = true;
+                    arg.value_parser(clap::value_parser!(i64))
+                }
+                openapi::ParamType::Number => {
+                    has_numeric_param = true;
+                    arg.value_parser(clap::value_parser!(f64))
+                }
+                openapi::ParamType::Boolean => arg.value_parser(BoolishValueParser::new()),
+                openapi::ParamType::Enum(values) => {
+                    arg.value_parser(PossibleValuesParser::new(values))
+                }
+                openapi::ParamType::String | openapi::ParamType::Json => arg,
             };
 
             cmd = cmd.arg(arg)
         }
 
+        if has_numeric_param {
+            // Otherwise clap treats a leading '-' on an integer/number value (e.g. `--offset -5`)
+            // as an unknown flag rather than a negative value.
+            cmd = cmd.allow_negative_numbers(true);
+        }
+
+        for binding in &endpoint.security {
+            let arg_name = format!("{}-token", binding.scheme_name);
+            let help = match binding.kind {
+                openapi::SecurityKind::HttpBasic => format!(
+                    "Credential for the '{}' security scheme, as 'user:pass' (falls back to {})",
+                    binding.scheme_name,
+                    http::security_env_var_name(&binding.scheme_name)
+                ),
+                _ => format!(
+                    "Credential for the '{}' security scheme (falls back to {})",
+                    binding.scheme_name,
+                    http::security_env_var_name(&binding.scheme_name)
+                ),
+            };
+            cmd = cmd.arg(
+                Arg::new(arg_name.clone())
+                    .long(arg_name)
+                    .required(false)
+                    .help(help),
+            );
+        }
+
         command = command.subcommand(cmd);
     }
 
@@ -106,30 +190,40 @@ fn get_initial_config() -> InitialConfig {
                 .required(false)
                 .help("override server from openapi config file"),
         )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .required(false)
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("connect-timeout")
+                .long("connect-timeout")
+                .required(false)
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("retries")
+                .long("retries")
+                .required(false)
+                .default_value("0")
+                .value_parser(clap::value_parser!(u32)),
+        )
         .disable_help_flag(true)
         .disable_help_subcommand(true)
         .ignore_errors(true);
 
     match initial_cmd.try_get_matches() {
-        Ok(matches) => {
-            let config = matches.get_one::<String>("config");
-            let server = matches.get_one::<String>("server");
-            match (config, server) {
-                (None, None) => InitialConfig::default(),
-                (None, Some(server)) => InitialConfig {
-                    config: DEFAULT_CONFIG.to_string(),
-                    server: Some(server.clone()),
-                },
-                (Some(config), None) => InitialConfig {
-                    config: config.clone(),
-                    server: None,
-                },
-                (Some(config), Some(server)) => InitialConfig {
-                    config: config.clone(),
-                    server: Some(server.clone()),
-                },
-            }
-        }
+        Ok(matches) => InitialConfig {
+            config: matches
+                .get_one::<String>("config")
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_CONFIG.to_string()),
+            server: matches.get_one::<String>("server").cloned(),
+            timeout_secs: matches.get_one::<u64>("timeout").copied(),
+            connect_timeout_secs: matches.get_one::<u64>("connect-timeout").copied(),
+            retries: matches.get_one::<u32>("retries").copied().unwrap_or(0),
+        },
         Err(_) => InitialConfig::default(),
     }
 }
@@ -140,6 +234,18 @@ async fn main() -> miette::Result<(), Errors> {
 
     let initial_config = get_initial_config();
 
+    // Build the shared HTTP client up front so --timeout/--connect-timeout apply to every request
+    let mut client_builder = reqwest::ClientBuilder::new();
+    if let Some(timeout_secs) = initial_config.timeout_secs {
+        client_builder = client_builder.timeout(std::time::Duration::from_secs(timeout_secs));
+    }
+    if let Some(connect_timeout_secs) = initial_config.connect_timeout_secs {
+        client_builder = client_builder
+            .connect_timeout(std::time::Duration::from_secs(connect_timeout_secs));
+    }
+    let client = client_builder.build()?;
+    let retries = initial_config.retries;
+
     // Parse OpenAPI spec
     // Extract endpoints
     let parsed_openapi = openapi::parse_endpoints(initial_config.config.as_str())?;
@@ -187,13 +293,46 @@ async fn main() -> miette::Result<(), Errors> {
         for endpoint in parsed_openapi.endpoints {
             if let Some(cmd_matches) = matches.subcommand_matches(&endpoint.name) {
                 ran_command = true;
-                let result =
-                    http::execute_request(endpoint, cmd_matches.clone(), &base_url).await?;
-                writeln!(
-                    std::io::stdout(),
-                    "{}",
-                    serde_json::to_string_pretty(&result)?
-                )?;
+                let include = cmd_matches.get_flag("include");
+                let response =
+                    http::execute_request(endpoint, cmd_matches.clone(), &base_url, &client, retries)
+                        .await?;
+
+                if include {
+                    writeln!(std::io::stdout(), "{}", response.status)?;
+                    for (name, value) in response.headers.iter() {
+                        writeln!(
+                            std::io::stdout(),
+                            "{}: {}",
+                            name,
+                            value.to_str().unwrap_or("")
+                        )?;
+                    }
+                    writeln!(std::io::stdout())?;
+                }
+
+                if response.status.is_success() {
+                    match &response.body {
+                        http::ResponseBody::Json(value) => {
+                            writeln!(std::io::stdout(), "{}", serde_json::to_string_pretty(value)?)?;
+                        }
+                        http::ResponseBody::Bytes(bytes) => {
+                            std::io::stdout().write_all(bytes)?;
+                        }
+                    }
+                } else {
+                    error!("request failed with status {}", response.status);
+                    match &response.body {
+                        http::ResponseBody::Json(value) => {
+                            eprintln!("{}", serde_json::to_string_pretty(value)?);
+                        }
+                        http::ResponseBody::Bytes(bytes) => {
+                            std::io::stderr().write_all(bytes)?;
+                        }
+                    }
+                    std::process::exit(1);
+                }
+
                 return Ok(());
             }
         }
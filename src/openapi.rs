@@ -1,6 +1,10 @@
 use crate::errors::Errors;
+use openapiv3::Components;
 use openapiv3::OpenAPI;
 use openapiv3::PathItem;
+use openapiv3::ReferenceOr;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fs;
 
 #[derive(Debug, Clone)]
@@ -9,6 +13,30 @@ pub struct Endpoint {
     pub method: String,
     pub path: String,
     pub params: Vec<Parameter>,
+    pub security: Vec<SecurityBinding>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SecurityBinding {
+    pub scheme_name: String,
+    pub kind: SecurityKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum SecurityKind {
+    HttpBasic,
+    HttpBearer,
+    ApiKey {
+        location: ApiKeyLocation,
+        key_name: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum ApiKeyLocation {
+    Header,
+    Query,
+    Cookie,
 }
 
 #[derive(Debug, Clone)]
@@ -16,7 +44,17 @@ pub struct Parameter {
     pub name: String,
     pub location: ParameterLocation,
     pub required: bool,
-    pub param_type: String,
+    pub param_type: ParamType,
+}
+
+#[derive(Debug, Clone)]
+pub enum ParamType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Enum(Vec<String>),
+    Json,
 }
 
 #[derive(Debug, Clone)]
@@ -25,6 +63,7 @@ pub enum ParameterLocation {
     Body,
     Path,
     Header,
+    Cookie,
 }
 
 pub struct ParsedSpec {
@@ -42,101 +81,267 @@ pub fn parse_endpoints(spec_path: &str) -> miette::Result<ParsedSpec, Errors> {
     tracing::debug!("parsing endpoints for {}", spec_path);
 
     let spec = parse_spec(spec_path)?;
-    let endpoints = extract_endpoints(&spec);
+    let endpoints = extract_endpoints(&spec)?;
     Ok(ParsedSpec { spec, endpoints })
 }
 
-fn extract_endpoints(spec: &OpenAPI) -> Vec<Endpoint> {
+fn extract_endpoints(spec: &OpenAPI) -> miette::Result<Vec<Endpoint>, Errors> {
     let mut endpoints = Vec::new();
+    let components = spec.components.as_ref();
+    let security_schemes = resolve_security_schemes(components);
 
     for (path, path_item) in spec.paths.clone().into_iter() {
         match path_item.into_item() {
             Some(path_item) => {
-                add_endpoint_for_method("get", &path, &path_item, &mut endpoints);
-                add_endpoint_for_method("post", &path, &path_item, &mut endpoints);
-                add_endpoint_for_method("put", &path, &path_item, &mut endpoints);
-                add_endpoint_for_method("delete", &path, &path_item, &mut endpoints);
+                for method in [
+                    "get", "post", "put", "delete", "patch", "head", "options", "trace",
+                ] {
+                    add_endpoint_for_method(
+                        method,
+                        &path,
+                        &path_item,
+                        components,
+                        &security_schemes,
+                        &spec.security,
+                        &mut endpoints,
+                    )?;
+                }
             }
             None => {}
         }
     }
 
     tracing::debug!("{} endpoints found", endpoints.len());
-    endpoints
+    Ok(endpoints)
+}
+
+// Resolves `components.securitySchemes` into the subset of schemes we can represent as a CLI
+// credential; OAuth2 and OpenID Connect flows have no static secret to pass on the command line,
+// so operations that only declare those schemes end up with no security bindings.
+fn resolve_security_schemes(components: Option<&Components>) -> HashMap<String, SecurityKind> {
+    let mut schemes = HashMap::new();
+    let Some(components) = components else {
+        return schemes;
+    };
+
+    for (name, scheme_ref) in &components.security_schemes {
+        let Some(scheme) = scheme_ref.as_item() else {
+            continue;
+        };
+
+        let kind = match scheme {
+            openapiv3::SecurityScheme::HTTP { scheme, .. } if scheme == "basic" => {
+                Some(SecurityKind::HttpBasic)
+            }
+            openapiv3::SecurityScheme::HTTP { scheme, .. } if scheme == "bearer" => {
+                Some(SecurityKind::HttpBearer)
+            }
+            openapiv3::SecurityScheme::APIKey { location, name, .. } => {
+                let location = match location {
+                    openapiv3::APIKeyLocation::Header => ApiKeyLocation::Header,
+                    openapiv3::APIKeyLocation::Query => ApiKeyLocation::Query,
+                    openapiv3::APIKeyLocation::Cookie => ApiKeyLocation::Cookie,
+                };
+                Some(SecurityKind::ApiKey {
+                    location,
+                    key_name: name.clone(),
+                })
+            }
+            _ => None,
+        };
+
+        if let Some(kind) = kind {
+            schemes.insert(name.clone(), kind);
+        }
+    }
+
+    schemes
+}
+
+// An operation's own `security` overrides the root-level requirement entirely when present (even
+// an empty list, meaning "no auth"); only schemes actually listed get attached to the endpoint.
+fn resolve_endpoint_security(
+    operation_security: &Option<Vec<openapiv3::SecurityRequirement>>,
+    root_security: &Option<Vec<openapiv3::SecurityRequirement>>,
+    security_schemes: &HashMap<String, SecurityKind>,
+) -> Vec<SecurityBinding> {
+    let requirements = match operation_security.as_ref().or(root_security.as_ref()) {
+        Some(requirements) => requirements,
+        None => return Vec::new(),
+    };
+
+    let mut seen = HashSet::new();
+    let mut bindings = Vec::new();
+    for requirement in requirements {
+        for scheme_name in requirement.keys() {
+            if !seen.insert(scheme_name.clone()) {
+                continue;
+            }
+            if let Some(kind) = security_schemes.get(scheme_name) {
+                bindings.push(SecurityBinding {
+                    scheme_name: scheme_name.clone(),
+                    kind: kind.clone(),
+                });
+            }
+        }
+    }
+    bindings
+}
+
+// Follows a `ReferenceOr` through `$ref` indirection using `lookup` to resolve each reference's
+// JSON-pointer to the next value, guarding against cycles with a visited set.
+fn resolve_reference<T: Clone>(
+    reference_or: &ReferenceOr<T>,
+    lookup: impl Fn(&str) -> Option<ReferenceOr<T>>,
+) -> miette::Result<T, Errors> {
+    let mut current = reference_or.clone();
+    let mut visited = HashSet::new();
+    loop {
+        match current {
+            ReferenceOr::Item(item) => return Ok(item),
+            ReferenceOr::Reference { reference } => {
+                if !visited.insert(reference.clone()) {
+                    return Err(Errors::UnresolvedReferenceError { reference });
+                }
+                current = lookup(&reference)
+                    .ok_or_else(|| Errors::UnresolvedReferenceError {
+                        reference: reference.clone(),
+                    })?;
+            }
+        }
+    }
+}
+
+fn resolve_parameter(
+    reference_or: &ReferenceOr<openapiv3::Parameter>,
+    components: Option<&Components>,
+) -> miette::Result<openapiv3::Parameter, Errors> {
+    resolve_reference(reference_or, |reference| {
+        let name = reference.strip_prefix("#/components/parameters/")?;
+        components.and_then(|c| c.parameters.get(name).cloned())
+    })
+}
+
+fn resolve_request_body(
+    reference_or: &ReferenceOr<openapiv3::RequestBody>,
+    components: Option<&Components>,
+) -> miette::Result<openapiv3::RequestBody, Errors> {
+    resolve_reference(reference_or, |reference| {
+        let name = reference.strip_prefix("#/components/requestBodies/")?;
+        components.and_then(|c| c.request_bodies.get(name).cloned())
+    })
 }
 
-fn parse_params(ps: &Vec<openapiv3::ReferenceOr<openapiv3::Parameter>>) -> Vec<Parameter> {
+// Maps a parameter's schema to the clap value type/validator it should use, so e.g. integers and
+// enums are rejected by clap before an HTTP call is ever made.
+fn infer_param_type(format: &openapiv3::ParameterSchemaOrContent) -> ParamType {
+    let schema_kind = match format {
+        openapiv3::ParameterSchemaOrContent::Schema(schema) => {
+            schema.as_item().map(|schema| &schema.schema_kind)
+        }
+        openapiv3::ParameterSchemaOrContent::Content(_) => None,
+    };
+
+    match schema_kind {
+        Some(openapiv3::SchemaKind::Type(openapiv3::Type::Integer(_))) => ParamType::Integer,
+        Some(openapiv3::SchemaKind::Type(openapiv3::Type::Number(_))) => ParamType::Number,
+        Some(openapiv3::SchemaKind::Type(openapiv3::Type::Boolean { .. })) => ParamType::Boolean,
+        Some(openapiv3::SchemaKind::Type(openapiv3::Type::String(string_type))) => {
+            let values: Vec<String> = string_type.enumeration.iter().flatten().cloned().collect();
+            if values.is_empty() {
+                ParamType::String
+            } else {
+                ParamType::Enum(values)
+            }
+        }
+        _ => ParamType::String,
+    }
+}
+
+fn parse_params(
+    ps: &Vec<ReferenceOr<openapiv3::Parameter>>,
+    components: Option<&Components>,
+) -> miette::Result<Vec<Parameter>, Errors> {
     tracing::debug!("parsing {} params", ps.len());
     let mut params = Vec::new();
     for param in ps {
-        match param.as_item() {
-            Some(paramx) => {
-                match paramx {
-                    openapiv3::Parameter::Query {
-                        parameter_data,
-                        allow_reserved: _,
-                        style: _,
-                        allow_empty_value: _,
-                    } => {
-                        tracing::trace!("Adding query param {:?}", parameter_data.name);
-                        params.push(Parameter {
-                            name: parameter_data.name.clone(),
-                            location: ParameterLocation::Query,
-                            required: parameter_data.required,
-                            param_type: "string".to_string(), // Simplified type handling
-                        });
-                    }
-                    openapiv3::Parameter::Header {
-                        parameter_data,
-                        style: _,
-                    } => {
-                        tracing::trace!("Adding header param {:?}", parameter_data.name);
-                        params.push(Parameter {
-                            name: parameter_data.name.clone(),
-                            location: ParameterLocation::Header,
-                            required: parameter_data.required,
-                            param_type: "string".to_string(), // Simplified type handling
-                        });
-                    }
-                    openapiv3::Parameter::Path {
-                        parameter_data,
-                        style: _,
-                    } => {
-                        tracing::trace!("Adding path param {:?}", parameter_data.name);
-                        params.push(Parameter {
-                            name: parameter_data.name.clone(),
-                            location: ParameterLocation::Path,
-                            required: parameter_data.required,
-                            param_type: "string".to_string(), // Simplified type handling
-                        });
-                    }
-                    openapiv3::Parameter::Cookie {
-                        parameter_data: _,
-                        style: _,
-                    } => todo!(),
-                };
+        let paramx = resolve_parameter(param, components)?;
+        match paramx {
+            openapiv3::Parameter::Query {
+                parameter_data,
+                allow_reserved: _,
+                style: _,
+                allow_empty_value: _,
+            } => {
+                tracing::trace!("Adding query param {:?}", parameter_data.name);
+                params.push(Parameter {
+                    name: parameter_data.name.clone(),
+                    location: ParameterLocation::Query,
+                    required: parameter_data.required,
+                    param_type: infer_param_type(&parameter_data.format),
+                });
             }
-            None => {
-                todo!()
+            openapiv3::Parameter::Header {
+                parameter_data,
+                style: _,
+            } => {
+                tracing::trace!("Adding header param {:?}", parameter_data.name);
+                params.push(Parameter {
+                    name: parameter_data.name.clone(),
+                    location: ParameterLocation::Header,
+                    required: parameter_data.required,
+                    param_type: infer_param_type(&parameter_data.format),
+                });
             }
-        }
+            openapiv3::Parameter::Path {
+                parameter_data,
+                style: _,
+            } => {
+                tracing::trace!("Adding path param {:?}", parameter_data.name);
+                params.push(Parameter {
+                    name: parameter_data.name.clone(),
+                    location: ParameterLocation::Path,
+                    required: parameter_data.required,
+                    param_type: infer_param_type(&parameter_data.format),
+                });
+            }
+            openapiv3::Parameter::Cookie {
+                parameter_data,
+                style: _,
+            } => {
+                tracing::trace!("Adding cookie param {:?}", parameter_data.name);
+                params.push(Parameter {
+                    name: parameter_data.name.clone(),
+                    location: ParameterLocation::Cookie,
+                    required: parameter_data.required,
+                    param_type: infer_param_type(&parameter_data.format),
+                });
+            }
+        };
     }
     tracing::debug!("added {:?} params", params.len());
-    params
+    Ok(params)
 }
 
 fn add_endpoint_for_method(
     method: &str,
     path: &str,
     path_item: &PathItem,
+    components: Option<&Components>,
+    security_schemes: &HashMap<String, SecurityKind>,
+    root_security: &Option<Vec<openapiv3::SecurityRequirement>>,
     endpoints: &mut Vec<Endpoint>,
-) {
+) -> miette::Result<(), Errors> {
     tracing::debug!("adding {} endpoint for path {}", method, path);
     let operation = match method {
         "get" => path_item.get.as_ref(),
         "post" => path_item.post.as_ref(),
         "put" => path_item.put.as_ref(),
         "delete" => path_item.delete.as_ref(),
+        "patch" => path_item.patch.as_ref(),
+        "head" => path_item.head.as_ref(),
+        "options" => path_item.options.as_ref(),
+        "trace" => path_item.trace.as_ref(),
         _ => None,
     };
 
@@ -146,30 +351,32 @@ fn add_endpoint_for_method(
             .clone()
             .unwrap_or_else(|| format!("{}_{}", method, path.replace("/", "_")));
 
-        let mut parsed_params = parse_params(&op.parameters);
+        let mut parsed_params = parse_params(&op.parameters, components)?;
 
         // Handle request body if present
         if let Some(request_body) = &op.request_body {
-            match request_body.clone().into_item() {
-                Some(rb) => {
-                    tracing::trace!("Adding body param");
-                    parsed_params.push(Parameter {
-                        name: "body".to_string(),
-                        location: ParameterLocation::Body,
-                        required: rb.required,
-                        param_type: "json".to_string(),
-                    });
-                }
-                None => todo!(),
-            }
+            let rb = resolve_request_body(request_body, components)?;
+            tracing::trace!("Adding body param");
+            parsed_params.push(Parameter {
+                name: "body".to_string(),
+                location: ParameterLocation::Body,
+                required: rb.required,
+                param_type: ParamType::Json,
+            });
         }
 
+        let security =
+            resolve_endpoint_security(&op.security, root_security, security_schemes);
+
         tracing::debug!("params for id {:?} and path {} parsed", name, path);
         endpoints.push(Endpoint {
             name,
             method: method.to_string(),
             path: path.to_string(),
             params: parsed_params,
+            security,
         });
     }
+
+    Ok(())
 }